@@ -1,4 +1,5 @@
 use crate::info::Info;
+use crate::operators::xpath::XPathCache;
 use crate::operators::{OperatorResult, Operators};
 use crate::request::{PortRange, Requests};
 use crate::results::MatchEvent;
@@ -26,27 +27,37 @@ impl ClusteredOperator {
   }
   pub fn matcher(&self, results: &mut MatchEvent) {
     let response = results.response().unwrap_or_default();
+    // Scoped to this one pass over `self.operators` (one per clustered
+    // template) so an XPath body shared across templates is parsed once
+    // instead of once per template, without keeping anything cached beyond
+    // this call.
+    let xpath_cache = XPathCache::default();
     for operator in self.operators.iter() {
       let mut result = OperatorResult::default();
-      if let Err(_err) = operator.matcher(&response, &mut result) {
+      if let Err(_err) = operator.matcher(&response, &mut result, &xpath_cache) {
         continue;
       };
-      operator.extractor(self.info.get_version(), &response, &mut result);
-      if result.is_matched() || result.is_extract() {
+      operator.extractor(self.info.get_version(), &response, &mut result, &xpath_cache);
+      // With diagnostics on, also surface a near-miss: nothing matched or
+      // extracted, but there's a trace explaining why each matcher failed.
+      // Without this, `Operators::diagnostics` populates `match_trace` and
+      // then every author-visible path drops it on the floor.
+      if result.is_matched() || result.is_extract() || (operator.diagnostics && !result.match_trace().is_empty()) {
         results.push(&self.template, &self.info, result);
       }
     }
   }
-  
+
   /// Match against a Request with optional Response for extensions
   pub fn matcher_request(&self, request: &Request, response: Option<&Response>, results: &mut MatchEvent) {
+    let xpath_cache = XPathCache::default();
     for operator in self.operators.iter() {
       let mut result = OperatorResult::default();
-      if let Err(_err) = operator.matcher_generic(request, response, &mut result) {
+      if let Err(_err) = operator.matcher_generic(request, response, &mut result, &xpath_cache) {
         continue;
       };
-      operator.extractor_generic(self.info.get_version(), request, &mut result);
-      if result.is_matched() || result.is_extract() {
+      operator.extractor_generic(self.info.get_version(), request, response, &mut result, &xpath_cache);
+      if result.is_matched() || result.is_extract() || (operator.diagnostics && !result.match_trace().is_empty()) {
         results.push(&self.template, &self.info, result);
       }
     }
@@ -0,0 +1,520 @@
+//! A small expression engine backing `MatcherType::DSL` / `ExtractorType::DSL`.
+//!
+//! Expressions are parsed once at `compile()` time into an AST and evaluated
+//! against an [`OperatorTarget`] plus any values already extracted for the
+//! current fingerprint. Unbound variables and type mismatches are reported as
+//! [`Error`] rather than panicking, so a bad expression just fails to match.
+
+use crate::error::Error;
+use crate::operators::target::OperatorTarget;
+use std::collections::{BTreeMap, HashSet};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+  Str(String),
+  Int(i64),
+  Bool(bool),
+  Bytes(Vec<u8>),
+}
+
+impl Value {
+  /// Coerces this value to a bool the way `&&`/`||`/`!` and DSL matchers do:
+  /// `Bool` is itself, `Int` is truthy when non-zero, `Str`/`Bytes` are
+  /// truthy when non-empty. Never fails, since every variant has a sensible
+  /// truthiness, so an expression like `len(body)` or a bare header lookup
+  /// matches instead of silently never matching.
+  pub fn as_bool(&self) -> Result<bool, Error> {
+    Ok(match self {
+      Value::Bool(b) => *b,
+      Value::Int(i) => *i != 0,
+      Value::Str(s) => !s.is_empty(),
+      Value::Bytes(b) => !b.is_empty(),
+    })
+  }
+
+  fn as_str(&self) -> Result<String, Error> {
+    match self {
+      Value::Str(s) => Ok(s.clone()),
+      Value::Bytes(b) => Ok(String::from_utf8_lossy(b).to_string()),
+      Value::Int(i) => Ok(i.to_string()),
+      Value::Bool(b) => Ok(b.to_string()),
+    }
+  }
+
+  pub fn stringify(&self) -> String {
+    match self {
+      Value::Str(s) => s.clone(),
+      Value::Int(i) => i.to_string(),
+      Value::Bool(b) => b.to_string(),
+      Value::Bytes(b) => String::from_utf8_lossy(b).to_string(),
+    }
+  }
+}
+
+fn dsl_error(msg: String) -> Error {
+  Error::IO(std::io::Error::new(std::io::ErrorKind::InvalidData, msg))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+  Ident(String),
+  Str(String),
+  Int(i64),
+  Eq,
+  Ne,
+  Lt,
+  Gt,
+  And,
+  Or,
+  Not,
+  LParen,
+  RParen,
+  LBracket,
+  RBracket,
+  Comma,
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, Error> {
+  let chars: Vec<char> = src.chars().collect();
+  let mut tokens = Vec::new();
+  let mut i = 0;
+  while i < chars.len() {
+    let c = chars[i];
+    match c {
+      ' ' | '\t' | '\n' | '\r' => i += 1,
+      '(' => {
+        tokens.push(Token::LParen);
+        i += 1;
+      }
+      ')' => {
+        tokens.push(Token::RParen);
+        i += 1;
+      }
+      '[' => {
+        tokens.push(Token::LBracket);
+        i += 1;
+      }
+      ']' => {
+        tokens.push(Token::RBracket);
+        i += 1;
+      }
+      ',' => {
+        tokens.push(Token::Comma);
+        i += 1;
+      }
+      '=' if chars.get(i + 1) == Some(&'=') => {
+        tokens.push(Token::Eq);
+        i += 2;
+      }
+      '!' if chars.get(i + 1) == Some(&'=') => {
+        tokens.push(Token::Ne);
+        i += 2;
+      }
+      '!' => {
+        tokens.push(Token::Not);
+        i += 1;
+      }
+      '<' => {
+        tokens.push(Token::Lt);
+        i += 1;
+      }
+      '>' => {
+        tokens.push(Token::Gt);
+        i += 1;
+      }
+      '&' if chars.get(i + 1) == Some(&'&') => {
+        tokens.push(Token::And);
+        i += 2;
+      }
+      '|' if chars.get(i + 1) == Some(&'|') => {
+        tokens.push(Token::Or);
+        i += 2;
+      }
+      '"' | '\'' => {
+        let quote = c;
+        let mut s = String::new();
+        i += 1;
+        while i < chars.len() && chars[i] != quote {
+          s.push(chars[i]);
+          i += 1;
+        }
+        if i >= chars.len() {
+          return Err(dsl_error(format!("unterminated string literal in `{src}`")));
+        }
+        i += 1;
+        tokens.push(Token::Str(s));
+      }
+      c if c.is_ascii_digit() => {
+        let mut s = String::new();
+        while i < chars.len() && chars[i].is_ascii_digit() {
+          s.push(chars[i]);
+          i += 1;
+        }
+        let n: i64 = s
+          .parse()
+          .map_err(|_| dsl_error(format!("invalid integer literal `{s}`")))?;
+        tokens.push(Token::Int(n));
+      }
+      c if c.is_alphabetic() || c == '_' => {
+        let mut s = String::new();
+        while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+          s.push(chars[i]);
+          i += 1;
+        }
+        tokens.push(Token::Ident(s));
+      }
+      other => return Err(dsl_error(format!("unexpected character `{other}` in `{src}`"))),
+    }
+  }
+  Ok(tokens)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum BinOp {
+  Eq,
+  Ne,
+  Lt,
+  Gt,
+  And,
+  Or,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+  Lit(Value),
+  Ident(String),
+  Index(String, Box<Expr>),
+  Not(Box<Expr>),
+  Bin(Box<Expr>, BinOp, Box<Expr>),
+  Call(String, Vec<Expr>),
+}
+
+struct Parser {
+  tokens: Vec<Token>,
+  pos: usize,
+}
+
+impl Parser {
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.pos)
+  }
+
+  fn next(&mut self) -> Option<Token> {
+    let t = self.tokens.get(self.pos).cloned();
+    self.pos += 1;
+    t
+  }
+
+  fn expect(&mut self, tok: &Token) -> Result<(), Error> {
+    match self.next() {
+      Some(ref t) if t == tok => Ok(()),
+      other => Err(dsl_error(format!("expected {tok:?}, got {other:?}"))),
+    }
+  }
+
+  fn parse_expr(&mut self) -> Result<Expr, Error> {
+    self.parse_or()
+  }
+
+  fn parse_or(&mut self) -> Result<Expr, Error> {
+    let mut lhs = self.parse_and()?;
+    while matches!(self.peek(), Some(Token::Or)) {
+      self.next();
+      let rhs = self.parse_and()?;
+      lhs = Expr::Bin(Box::new(lhs), BinOp::Or, Box::new(rhs));
+    }
+    Ok(lhs)
+  }
+
+  fn parse_and(&mut self) -> Result<Expr, Error> {
+    let mut lhs = self.parse_cmp()?;
+    while matches!(self.peek(), Some(Token::And)) {
+      self.next();
+      let rhs = self.parse_cmp()?;
+      lhs = Expr::Bin(Box::new(lhs), BinOp::And, Box::new(rhs));
+    }
+    Ok(lhs)
+  }
+
+  fn parse_cmp(&mut self) -> Result<Expr, Error> {
+    let lhs = self.parse_unary()?;
+    let op = match self.peek() {
+      Some(Token::Eq) => BinOp::Eq,
+      Some(Token::Ne) => BinOp::Ne,
+      Some(Token::Lt) => BinOp::Lt,
+      Some(Token::Gt) => BinOp::Gt,
+      _ => return Ok(lhs),
+    };
+    self.next();
+    let rhs = self.parse_unary()?;
+    Ok(Expr::Bin(Box::new(lhs), op, Box::new(rhs)))
+  }
+
+  fn parse_unary(&mut self) -> Result<Expr, Error> {
+    if matches!(self.peek(), Some(Token::Not)) {
+      self.next();
+      return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+    }
+    self.parse_primary()
+  }
+
+  fn parse_primary(&mut self) -> Result<Expr, Error> {
+    match self.next() {
+      Some(Token::Str(s)) => Ok(Expr::Lit(Value::Str(s))),
+      Some(Token::Int(n)) => Ok(Expr::Lit(Value::Int(n))),
+      Some(Token::LParen) => {
+        let e = self.parse_expr()?;
+        self.expect(&Token::RParen)?;
+        Ok(e)
+      }
+      Some(Token::Ident(name)) => {
+        if name == "true" {
+          return Ok(Expr::Lit(Value::Bool(true)));
+        }
+        if name == "false" {
+          return Ok(Expr::Lit(Value::Bool(false)));
+        }
+        match self.peek() {
+          Some(Token::LParen) => {
+            self.next();
+            let mut args = Vec::new();
+            if !matches!(self.peek(), Some(Token::RParen)) {
+              args.push(self.parse_expr()?);
+              while matches!(self.peek(), Some(Token::Comma)) {
+                self.next();
+                args.push(self.parse_expr()?);
+              }
+            }
+            self.expect(&Token::RParen)?;
+            Ok(Expr::Call(name, args))
+          }
+          Some(Token::LBracket) => {
+            self.next();
+            let index = self.parse_expr()?;
+            self.expect(&Token::RBracket)?;
+            Ok(Expr::Index(name, Box::new(index)))
+          }
+          _ => Ok(Expr::Ident(name)),
+        }
+      }
+      other => Err(dsl_error(format!("unexpected token {other:?}"))),
+    }
+  }
+}
+
+/// A compiled DSL expression, ready for repeated evaluation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DslExpr {
+  raw: String,
+  ast: Expr,
+}
+
+impl DslExpr {
+  pub fn compile(src: &str) -> Result<Self, Error> {
+    let tokens = tokenize(src)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let ast = parser.parse_expr()?;
+    if parser.pos != parser.tokens.len() {
+      return Err(dsl_error(format!("trailing tokens after expression `{src}`")));
+    }
+    Ok(Self { raw: src.to_string(), ast })
+  }
+
+  pub fn raw(&self) -> &str {
+    &self.raw
+  }
+
+  pub fn eval<T: OperatorTarget>(
+    &self,
+    target: &T,
+    status_code: Option<u16>,
+    extract_result: &BTreeMap<String, HashSet<String>>,
+  ) -> Result<Value, Error> {
+    eval(&self.ast, target, status_code, extract_result)
+  }
+}
+
+fn resolve_ident<T: OperatorTarget>(
+  name: &str,
+  target: &T,
+  status_code: Option<u16>,
+  extract_result: &BTreeMap<String, HashSet<String>>,
+) -> Result<Value, Error> {
+  match name {
+    "status_code" => status_code
+      .map(|c| Value::Int(c as i64))
+      .ok_or_else(|| dsl_error("status_code is not available for this target".to_string())),
+    "content_length" => Ok(Value::Int(target.get_raw_body().len() as i64)),
+    "body" => Ok(Value::Str(target.get_body().unwrap_or_default())),
+    other => extract_result
+      .get(other)
+      .and_then(|values| values.iter().next())
+      .map(|v| Value::Str(v.clone()))
+      .ok_or_else(|| dsl_error(format!("unbound variable `{other}`"))),
+  }
+}
+
+fn eval<T: OperatorTarget>(
+  expr: &Expr,
+  target: &T,
+  status_code: Option<u16>,
+  extract_result: &BTreeMap<String, HashSet<String>>,
+) -> Result<Value, Error> {
+  match expr {
+    Expr::Lit(v) => Ok(v.clone()),
+    Expr::Ident(name) => resolve_ident(name, target, status_code, extract_result),
+    Expr::Index(base, index) => {
+      let key = eval(index, target, status_code, extract_result)?.as_str()?;
+      match base.as_str() {
+        "header" => target
+          .get_header(&key)
+          .map(Value::Str)
+          .ok_or_else(|| dsl_error(format!("unbound header `{key}`"))),
+        other => Err(dsl_error(format!("`{other}` is not indexable"))),
+      }
+    }
+    Expr::Not(inner) => Ok(Value::Bool(!eval(inner, target, status_code, extract_result)?.as_bool()?)),
+    Expr::Bin(lhs, op, rhs) => {
+      let l = eval(lhs, target, status_code, extract_result)?;
+      match op {
+        BinOp::And => {
+          if !l.as_bool()? {
+            return Ok(Value::Bool(false));
+          }
+          Ok(Value::Bool(eval(rhs, target, status_code, extract_result)?.as_bool()?))
+        }
+        BinOp::Or => {
+          if l.as_bool()? {
+            return Ok(Value::Bool(true));
+          }
+          Ok(Value::Bool(eval(rhs, target, status_code, extract_result)?.as_bool()?))
+        }
+        BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Gt => {
+          let r = eval(rhs, target, status_code, extract_result)?;
+          let cmp = match (&l, &r) {
+            (Value::Int(a), Value::Int(b)) => a.cmp(b),
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::Str(a), Value::Str(b)) => a.cmp(b),
+            (Value::Bytes(a), Value::Bytes(b)) => a.cmp(b),
+            _ => return Err(dsl_error(format!("type mismatch comparing {l:?} and {r:?}"))),
+          };
+          Ok(Value::Bool(match op {
+            BinOp::Eq => cmp == std::cmp::Ordering::Equal,
+            BinOp::Ne => cmp != std::cmp::Ordering::Equal,
+            BinOp::Lt => cmp == std::cmp::Ordering::Less,
+            BinOp::Gt => cmp == std::cmp::Ordering::Greater,
+            _ => unreachable!(),
+          }))
+        }
+      }
+    }
+    Expr::Call(name, args) => {
+      let values: Vec<Value> = args
+        .iter()
+        .map(|a| eval(a, target, status_code, extract_result))
+        .collect::<Result<_, _>>()?;
+      call(name, values)
+    }
+  }
+}
+
+fn call(name: &str, args: Vec<Value>) -> Result<Value, Error> {
+  match name {
+    "contains" => {
+      let (s, sub) = (arg_str(&args, 0)?, arg_str(&args, 1)?);
+      Ok(Value::Bool(s.contains(&sub)))
+    }
+    "regex" => {
+      let (pat, s) = (arg_str(&args, 0)?, arg_str(&args, 1)?);
+      let re = regex::Regex::new(&pat).map_err(|e| dsl_error(format!("invalid regex `{pat}`: {e}")))?;
+      Ok(Value::Bool(re.is_match(&s)))
+    }
+    "len" => Ok(Value::Int(arg_str(&args, 0)?.len() as i64)),
+    "to_lower" => Ok(Value::Str(arg_str(&args, 0)?.to_lowercase())),
+    "md5" => {
+      let digest = md5::compute(arg_str(&args, 0)?.as_bytes());
+      Ok(Value::Str(format!("{digest:x}")))
+    }
+    "hex_decode" => {
+      let s = arg_str(&args, 0)?;
+      let bytes = hex::decode(&s).map_err(|e| dsl_error(format!("invalid hex string `{s}`: {e}")))?;
+      Ok(Value::Bytes(bytes))
+    }
+    other => Err(dsl_error(format!("unknown function `{other}`"))),
+  }
+}
+
+fn arg_str(args: &[Value], idx: usize) -> Result<String, Error> {
+  args
+    .get(idx)
+    .ok_or_else(|| dsl_error(format!("missing argument {idx}")))?
+    .as_str()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn eval_src(src: &str) -> Result<Value, Error> {
+    let request = slinger::Request::builder()
+      .method("GET")
+      .uri("http://example.com/")
+      .header("X-Token", "abc123")
+      .body(slinger::Body::from("hello world"))
+      .unwrap();
+    let request = slinger::Request::from(request);
+    DslExpr::compile(src)?.eval(&request, Some(200), &BTreeMap::new())
+  }
+
+  #[test]
+  fn test_truthiness_of_non_bool_values() {
+    assert!(eval_src("len(body)").unwrap().as_bool().unwrap());
+    assert!(eval_src("body").unwrap().as_bool().unwrap());
+    assert!(!eval_src("0").unwrap().as_bool().unwrap());
+    assert!(!eval_src("\"\"").unwrap().as_bool().unwrap());
+  }
+
+  #[test]
+  fn test_and_or_not_use_truthiness_not_just_literal_bool() {
+    assert!(eval_src("1 && \"x\"").unwrap().as_bool().unwrap());
+    assert!(!eval_src("0 && \"x\"").unwrap().as_bool().unwrap());
+    assert!(eval_src("0 || \"x\"").unwrap().as_bool().unwrap());
+    assert!(!eval_src("0 || \"\"").unwrap().as_bool().unwrap());
+    assert!(eval_src("!0").unwrap().as_bool().unwrap());
+  }
+
+  #[test]
+  fn test_comparison_operators() {
+    assert!(eval_src("status_code == 200").unwrap().as_bool().unwrap());
+    assert!(eval_src("status_code != 404").unwrap().as_bool().unwrap());
+    assert!(eval_src("1 < 2").unwrap().as_bool().unwrap());
+    assert!(eval_src("2 > 1").unwrap().as_bool().unwrap());
+  }
+
+  #[test]
+  fn test_contains_and_regex_functions() {
+    assert!(eval_src("contains(body, \"world\")").unwrap().as_bool().unwrap());
+    assert!(!eval_src("contains(body, \"xyz\")").unwrap().as_bool().unwrap());
+    assert!(eval_src("regex(\"^hello\", body)").unwrap().as_bool().unwrap());
+    assert!(!eval_src("regex(\"^world\", body)").unwrap().as_bool().unwrap());
+  }
+
+  #[test]
+  fn test_header_index_lookup() {
+    assert_eq!(eval_src("header[\"x-token\"]").unwrap(), Value::Str("abc123".to_string()));
+  }
+
+  #[test]
+  fn test_unbound_variable_and_header_are_errors_not_panics() {
+    assert!(eval_src("nonexistent_var").is_err());
+    assert!(eval_src("header[\"missing\"]").is_err());
+  }
+
+  #[test]
+  fn test_comparing_mismatched_types_is_an_error_not_string_coercion() {
+    // `status_code` is an Int; comparing it against a Str must fail instead
+    // of silently stringifying both sides and matching "200" == "200".
+    assert!(eval_src("status_code == \"200\"").is_err());
+    assert!(eval_src("1 < \"2\"").is_err());
+  }
+}
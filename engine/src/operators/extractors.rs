@@ -0,0 +1,242 @@
+use crate::info::Version;
+use crate::operators::matchers::{Dsl, Part, XPath};
+use crate::operators::regex::Regex;
+use crate::operators::target::OperatorTarget;
+use crate::serde_format::is_default;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Json {
+  pub json: Vec<String>,
+}
+
+/// Which header/cookie keys to pull values out of.
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct KVal {
+  pub kval: Vec<String>,
+}
+
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ExtractorType {
+  Regex(Regex),
+  #[serde(rename = "json")]
+  JSON(Json),
+  #[serde(rename = "kval")]
+  KVal(KVal),
+  #[serde(rename = "xpath")]
+  XPath(XPath),
+  #[serde(rename = "dsl")]
+  DSL(Dsl),
+}
+
+impl Default for ExtractorType {
+  fn default() -> Self {
+    ExtractorType::Regex(Regex::default())
+  }
+}
+
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Extractor {
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub name: Option<String>,
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub part: Part,
+  #[serde(flatten, default)]
+  pub extractor_type: ExtractorType,
+}
+
+impl Extractor {
+  pub fn compile(&mut self) -> std::result::Result<(), regex::Error> {
+    match &mut self.extractor_type {
+      ExtractorType::Regex(re) => re.compile()?,
+      ExtractorType::DSL(dsl) => {
+        if let Err(err) = dsl.compile() {
+          return Err(regex::Error::Syntax(err.to_string()));
+        }
+      }
+      ExtractorType::XPath(xp) => {
+        if let Err(err) = xp.compile() {
+          return Err(regex::Error::Syntax(err.to_string()));
+        }
+      }
+      ExtractorType::JSON(_) | ExtractorType::KVal(_) => {}
+    }
+    Ok(())
+  }
+
+  pub fn extract_regex(
+    &self,
+    re: &Regex,
+    words: Vec<String>,
+    body: String,
+    _version: &Option<Version>,
+  ) -> (HashSet<String>, BTreeMap<String, String>) {
+    let _ = words;
+    let mut extract_result = HashSet::new();
+    for pattern in re.patterns() {
+      for caps in pattern.captures_iter(&body) {
+        if let Some(group) = caps.get(1).or_else(|| caps.get(0)) {
+          extract_result.insert(group.as_str().to_string());
+        }
+      }
+    }
+    (extract_result, BTreeMap::new())
+  }
+
+  pub fn extract_json(&self, json: &Json, words: Vec<String>) -> (HashSet<String>, BTreeMap<String, String>) {
+    let mut extract_result = HashSet::new();
+    for word in &words {
+      let Ok(value) = serde_json::from_str::<serde_json::Value>(word) else {
+        continue;
+      };
+      for path in &json.json {
+        if let Some(found) = jsonpath_value(&value, path) {
+          extract_result.insert(found);
+        }
+      }
+    }
+    (extract_result, BTreeMap::new())
+  }
+
+  /// Evaluates every expression in `dsl` against `target`, stringifying
+  /// whatever value each one produces. Evaluation errors (unbound variable,
+  /// type mismatch) drop that expression rather than failing extraction.
+  /// `status_code` mirrors `Matcher::match_dsl` - only available when the
+  /// extractor runs against a `Response` extension, `None` for a bare `Request`.
+  pub fn extract_dsl<T: OperatorTarget>(
+    &self,
+    dsl: &Dsl,
+    target: &T,
+    status_code: Option<u16>,
+    extract_result: &BTreeMap<String, HashSet<String>>,
+  ) -> HashSet<String> {
+    let mut out = HashSet::new();
+    for expr in dsl.expressions() {
+      if let Ok(value) = expr.eval(target, status_code, extract_result) {
+        out.insert(value.stringify());
+      }
+    }
+    out
+  }
+
+  /// Parses `body` as HTML/XML and collects the string value of every
+  /// matched node. `cache` is shared across an entire `ClusteredOperator`
+  /// pass, so a body already parsed for another clustered template's
+  /// extractor is reused instead of re-parsed.
+  pub fn extract_xpath(&self, xpath: &XPath, body: &str, cache: &crate::operators::xpath::XPathCache) -> HashSet<String> {
+    let doc = cache.get_or_parse(body);
+    let mut extract_result = HashSet::new();
+    for expr in xpath.expressions() {
+      extract_result.extend(expr.evaluate(&doc));
+    }
+    extract_result
+  }
+
+  /// Looks up each configured key case-insensitively among `target`'s
+  /// headers, falling back to parsing it as a cookie name out of
+  /// `Set-Cookie`/`Cookie`. Works identically for `Request` and `Response`
+  /// targets so `matcher_both` can pull auth tokens out of requests and
+  /// server/version headers out of responses in the same pass. Keyed by
+  /// kval key rather than a single extractor-wide bucket, since one
+  /// extractor can name several keys at once.
+  pub fn extract_kval<T: OperatorTarget>(&self, kval: &KVal, target: &T) -> BTreeMap<String, HashSet<String>> {
+    let mut extract_result: BTreeMap<String, HashSet<String>> = BTreeMap::new();
+    for key in &kval.kval {
+      let value = target.get_header(key).or_else(|| extract_cookie(target, key));
+      if let Some(value) = value {
+        extract_result.entry(key.clone()).or_default().insert(value);
+      }
+    }
+    extract_result
+  }
+}
+
+fn extract_cookie<T: OperatorTarget>(target: &T, name: &str) -> Option<String> {
+  // Servers send one `Set-Cookie` header per cookie, so every occurrence
+  // needs checking - `get_header` alone would only ever see the first.
+  for header_name in ["set-cookie", "cookie"] {
+    for raw in target.get_header_values(header_name) {
+      for pair in raw.split(';') {
+        let mut parts = pair.trim().splitn(2, '=');
+        let (Some(k), Some(v)) = (parts.next(), parts.next()) else {
+          continue;
+        };
+        if k.eq_ignore_ascii_case(name) {
+          return Some(v.to_string());
+        }
+      }
+    }
+  }
+  None
+}
+
+fn jsonpath_value(root: &serde_json::Value, path: &str) -> Option<String> {
+  match crate::operators::jsonpath::resolve(root, path)? {
+    serde_json::Value::String(s) => Some(s.clone()),
+    other => Some(other.to_string()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_extract_kval_from_header_and_cookie() {
+    let response = slinger::http::Response::builder()
+      .status(200)
+      .header("Server", "nginx/1.18.0")
+      .header("Set-Cookie", "session=abc123; Path=/")
+      .header("Set-Cookie", "csrf=def456; Path=/")
+      .body(slinger::Body::from("ok"))
+      .unwrap();
+    let response = slinger::Response::from(response);
+
+    let extractor = Extractor::default();
+    let kval = KVal { kval: vec!["server".to_string(), "csrf".to_string()] };
+    let extract_result = extractor.extract_kval(&kval, &response);
+
+    assert_eq!(
+      extract_result.get("server"),
+      Some(&HashSet::from(["nginx/1.18.0".to_string()]))
+    );
+    // "csrf" isn't a header name, so it falls back to scanning every
+    // Set-Cookie header for a cookie named "csrf" - not just the first one.
+    assert_eq!(
+      extract_result.get("csrf"),
+      Some(&HashSet::from(["def456".to_string()]))
+    );
+  }
+
+  #[test]
+  fn test_extract_dsl_against_response_including_status_code() {
+    let response = slinger::http::Response::builder()
+      .status(200)
+      .header("Server", "nginx/1.18.0")
+      .body(slinger::Body::from("ok"))
+      .unwrap();
+    let response = slinger::Response::from(response);
+
+    let mut dsl = Dsl { dsl: vec!["status_code".to_string(), "header[\"server\"]".to_string()], ..Default::default() };
+    dsl.compile().unwrap();
+    let extractor = Extractor::default();
+
+    // Without a status code (the bare-Request case), the `status_code`
+    // expression is an unbound variable and drops out of the result.
+    let extract_result = extractor.extract_dsl(&dsl, &response, None, &BTreeMap::new());
+    assert!(!extract_result.contains("200"));
+    assert!(extract_result.contains("nginx/1.18.0"));
+
+    // With the response's status code threaded through, it extracts too.
+    let extract_result = extractor.extract_dsl(&dsl, &response, Some(200), &BTreeMap::new());
+    assert!(extract_result.contains("200"));
+    assert!(extract_result.contains("nginx/1.18.0"));
+  }
+}
@@ -0,0 +1,25 @@
+//! A small dotted-path subset of JSONPath (`$.a.b`, `$.a[0].b`), enough for
+//! template matchers/extractors without pulling in a full JSONPath engine.
+
+use serde_json::Value;
+
+pub fn resolve<'a>(root: &'a Value, path: &str) -> Option<&'a Value> {
+  let path = path.strip_prefix('$').unwrap_or(path);
+  let mut current = root;
+  for segment in path.split('.') {
+    if segment.is_empty() {
+      continue;
+    }
+    let (name, index) = match segment.split_once('[') {
+      Some((name, rest)) => (name, rest.trim_end_matches(']').parse::<usize>().ok()),
+      None => (segment, None),
+    };
+    if !name.is_empty() {
+      current = current.get(name)?;
+    }
+    if let Some(index) = index {
+      current = current.get(index)?;
+    }
+  }
+  Some(current)
+}
@@ -0,0 +1,651 @@
+use crate::error::Result;
+use crate::operators::dsl::DslExpr;
+use crate::operators::jsonpath;
+use crate::operators::regex::Regex;
+use crate::operators::target::OperatorTarget;
+use crate::operators::xpath::{self, CompiledXPath};
+use crate::serde_format::is_default;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+fn json_scalar_string(value: &serde_json::Value) -> String {
+  match value {
+    serde_json::Value::String(s) => s.clone(),
+    other => other.to_string(),
+  }
+}
+
+/// The part of the target a matcher/extractor reads from.
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Part {
+  #[default]
+  Body,
+  Header,
+  All,
+}
+
+impl Part {
+  /// Collects the candidate strings to search (`words`) together with the
+  /// single concatenated text to run regexes against (`body`).
+  pub fn get_matcher_word_from_part<T: OperatorTarget>(&self, target: &T) -> Result<(Vec<String>, String)> {
+    match self {
+      Part::Body => {
+        let body = target.get_body().unwrap_or_default();
+        Ok((vec![body.clone()], body))
+      }
+      Part::Header => {
+        let headers: Vec<String> = target.get_headers().into_iter().collect();
+        let joined = headers.join("\n");
+        Ok((headers, joined))
+      }
+      Part::All => {
+        let mut words: Vec<String> = target.get_headers().into_iter().collect();
+        let body = target.get_body().unwrap_or_default();
+        words.push(body.clone());
+        let joined = format!("{}\n{}", words.join("\n"), body);
+        Ok((words, joined))
+      }
+    }
+  }
+}
+
+/// How multiple matchers/sub-conditions combine.
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Condition {
+  #[default]
+  Or,
+  And,
+}
+
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Word {
+  pub words: Vec<String>,
+}
+
+/// Favicon hash table entry, keyed by hash algorithm in `matcher_generic`.
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct FaviconMap {
+  pub hash: String,
+  pub name: String,
+}
+
+/// A single hex-encoded byte pattern, optionally pinned to a byte offset
+/// within the raw response. Plain `"deadbeef"` matches anywhere; `{binary:
+/// "deadbeef", offset: 4}` pins it to offset 4 - so a `Binary` matcher can
+/// require different patterns at different fixed offsets (e.g. distinct
+/// markers in a protocol banner) instead of every pattern sharing one offset.
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum BinaryPattern {
+  Hex(String),
+  WithOffset {
+    binary: String,
+    offset: usize,
+  },
+}
+
+impl BinaryPattern {
+  fn hex(&self) -> &str {
+    match self {
+      BinaryPattern::Hex(hex) => hex,
+      BinaryPattern::WithOffset { binary, .. } => binary,
+    }
+  }
+
+  fn offset(&self) -> Option<usize> {
+    match self {
+      BinaryPattern::Hex(_) => None,
+      BinaryPattern::WithOffset { offset, .. } => Some(*offset),
+    }
+  }
+}
+
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Binary {
+  /// Hex-encoded byte patterns to look for in the raw response body, each
+  /// optionally pinned to its own byte offset.
+  pub binary: Vec<BinaryPattern>,
+}
+
+impl Binary {
+  fn matches_at(haystack: &[u8], needle: &[u8], offset: Option<usize>) -> bool {
+    match offset {
+      // `offset` comes straight off the deserialized template, so a bare
+      // `offset + needle.len()` can overflow on a malformed/adversarial
+      // config (e.g. offset near `usize::MAX`); `checked_add` turns that
+      // into a clean non-match instead of a panic.
+      Some(offset) => offset
+        .checked_add(needle.len())
+        .and_then(|end| haystack.get(offset..end))
+        .is_some_and(|slice| slice == needle),
+      None => needle.is_empty() || haystack.windows(needle.len()).any(|window| window == needle),
+    }
+  }
+}
+
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct XPath {
+  pub xpath: Vec<String>,
+  /// Optional namespace prefix -> URI map for namespace-qualified queries.
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub namespaces: BTreeMap<String, String>,
+  #[serde(skip)]
+  #[cfg_attr(feature = "mcp", schemars(skip))]
+  compiled: Vec<CompiledXPath>,
+}
+
+impl PartialEq for XPath {
+  fn eq(&self, other: &Self) -> bool {
+    self.xpath == other.xpath && self.namespaces == other.namespaces
+  }
+}
+
+impl XPath {
+  pub fn compile(&mut self) -> Result<()> {
+    self.compiled = self
+      .xpath
+      .iter()
+      .map(|pattern| CompiledXPath::compile(pattern, &self.namespaces))
+      .collect::<Result<Vec<_>>>()?;
+    Ok(())
+  }
+
+  pub fn expressions(&self) -> &[CompiledXPath] {
+    &self.compiled
+  }
+}
+
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Dsl {
+  pub dsl: Vec<String>,
+  #[serde(skip)]
+  #[cfg_attr(feature = "mcp", schemars(skip))]
+  compiled: Vec<DslExpr>,
+}
+
+impl Dsl {
+  pub fn compile(&mut self) -> Result<()> {
+    self.compiled = self
+      .dsl
+      .iter()
+      .map(|src| DslExpr::compile(src))
+      .collect::<Result<Vec<_>>>()?;
+    Ok(())
+  }
+
+  pub fn expressions(&self) -> &[DslExpr] {
+    &self.compiled
+  }
+}
+
+/// The structural rule a single `JsonMatch` entry applies to whatever its
+/// JSONPath resolves to.
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "rule", rename_all = "kebab-case")]
+pub enum JsonRule {
+  Equals(serde_json::Value),
+  Type(JsonValueType),
+  Regex(String),
+  #[serde(rename = "arrayLength")]
+  ArrayLength(ArrayLengthRule),
+  Exists,
+}
+
+/// An array-length check: either an exact count (`arrayLength: 3`) or a
+/// comparison (`arrayLength: {gte: 3}`), so "an array of length >= 3" is
+/// expressible and not just exact-match.
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum ArrayLengthRule {
+  Exact(usize),
+  Cmp {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    eq: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gte: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lte: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gt: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lt: Option<usize>,
+  },
+}
+
+impl ArrayLengthRule {
+  fn matches(&self, len: usize) -> bool {
+    match self {
+      ArrayLengthRule::Exact(n) => len == *n,
+      ArrayLengthRule::Cmp { eq, gte, lte, gt, lt } => {
+        eq.map_or(true, |n| len == n)
+          && gte.map_or(true, |n| len >= n)
+          && lte.map_or(true, |n| len <= n)
+          && gt.map_or(true, |n| len > n)
+          && lt.map_or(true, |n| len < n)
+      }
+    }
+  }
+}
+
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonValueType {
+  String,
+  Number,
+  Bool,
+  Array,
+  Object,
+}
+
+impl JsonValueType {
+  fn matches(&self, value: &serde_json::Value) -> bool {
+    match self {
+      JsonValueType::String => value.is_string(),
+      JsonValueType::Number => value.is_number(),
+      JsonValueType::Bool => value.is_boolean(),
+      JsonValueType::Array => value.is_array(),
+      JsonValueType::Object => value.is_object(),
+    }
+  }
+}
+
+/// One `path` + `rule` pair evaluated against the parsed JSON body.
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonMatch {
+  pub path: String,
+  #[serde(flatten)]
+  pub rule: JsonRule,
+  #[serde(skip)]
+  #[cfg_attr(feature = "mcp", schemars(skip))]
+  compiled_regex: Option<regex::Regex>,
+}
+
+impl PartialEq for JsonMatch {
+  fn eq(&self, other: &Self) -> bool {
+    self.path == other.path && self.rule == other.rule
+  }
+}
+
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Json {
+  pub json: Vec<JsonMatch>,
+}
+
+impl Json {
+  pub fn compile(&mut self) -> std::result::Result<(), regex::Error> {
+    for entry in self.json.iter_mut() {
+      match &entry.rule {
+        JsonRule::Regex(pattern) => {
+          entry.compiled_regex = Some(regex::Regex::new(pattern)?);
+        }
+        JsonRule::ArrayLength(ArrayLengthRule::Cmp { eq, gte, lte, gt, lt }) => {
+          // Every field is optional, so `arrayLength: {}` deserializes fine
+          // but would otherwise match any length via the all-`map_or(true, ..)`
+          // chain in `ArrayLengthRule::matches` - reject it at compile time
+          // the same way a bad DSL/XPath pattern fails to compile.
+          if eq.is_none() && gte.is_none() && lte.is_none() && gt.is_none() && lt.is_none() {
+            return Err(regex::Error::Syntax(format!(
+              "arrayLength comparison for `{}` has no bound set (eq/gte/lte/gt/lt)",
+              entry.path
+            )));
+          }
+        }
+        _ => {}
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum MatcherType {
+  Word(Word),
+  Regex(Regex),
+  Status(Vec<u16>),
+  Favicon(String),
+  Binary(Binary),
+  #[serde(rename = "xpath")]
+  XPath(XPath),
+  #[serde(rename = "json")]
+  JSON(Json),
+  #[serde(rename = "dsl")]
+  DSL(Dsl),
+  None,
+}
+
+impl Default for MatcherType {
+  fn default() -> Self {
+    MatcherType::None
+  }
+}
+
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Matcher {
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub name: Option<String>,
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub part: Part,
+  #[serde(flatten, default)]
+  pub matcher_type: MatcherType,
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub condition: Condition,
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub negative: bool,
+}
+
+impl Matcher {
+  pub fn compile(&mut self) -> std::result::Result<(), regex::Error> {
+    match &mut self.matcher_type {
+      MatcherType::Regex(re) => re.compile()?,
+      MatcherType::DSL(dsl) => {
+        // DSL parse errors aren't `regex::Error`; surface them the same way
+        // nuclei-style templates surface a bad pattern, as a compile-time regex error.
+        if let Err(err) = dsl.compile() {
+          return Err(regex::Error::Syntax(err.to_string()));
+        }
+      }
+      MatcherType::XPath(xp) => {
+        if let Err(err) = xp.compile() {
+          return Err(regex::Error::Syntax(err.to_string()));
+        }
+      }
+      MatcherType::JSON(json) => json.compile()?,
+      MatcherType::Word(_) | MatcherType::Status(_) | MatcherType::Favicon(_) | MatcherType::Binary(_) | MatcherType::None => {}
+    }
+    Ok(())
+  }
+
+  /// Human-readable matcher kind and expected pattern, used to build
+  /// `MatchTrace` diagnostics without exposing the internal `MatcherType`.
+  pub fn describe(&self) -> (&'static str, String) {
+    match &self.matcher_type {
+      MatcherType::Word(w) => ("word", w.words.join(" | ")),
+      MatcherType::Regex(re) => ("regex", re.regex.join(" | ")),
+      MatcherType::Status(status) => ("status", status.iter().map(u16::to_string).collect::<Vec<_>>().join(", ")),
+      MatcherType::Favicon(hash) => ("favicon", hash.clone()),
+      MatcherType::Binary(bin) => ("binary", bin.binary.iter().map(BinaryPattern::hex).collect::<Vec<_>>().join(", ")),
+      MatcherType::XPath(xp) => ("xpath", xp.xpath.join(" | ")),
+      MatcherType::JSON(json) => (
+        "json",
+        json.json.iter().map(|entry| entry.path.clone()).collect::<Vec<_>>().join(" | "),
+      ),
+      MatcherType::DSL(dsl) => ("dsl", dsl.dsl.join(" | ")),
+      MatcherType::None => ("none", String::new()),
+    }
+  }
+
+  pub fn negative(&self, is_match: bool) -> bool {
+    if self.negative {
+      !is_match
+    } else {
+      is_match
+    }
+  }
+
+  pub fn match_word(&self, word: &Word, words: Vec<String>) -> (bool, Vec<String>) {
+    let mut matched = Vec::new();
+    for w in &word.words {
+      if words.iter().any(|candidate| candidate.contains(w)) {
+        matched.push(w.clone());
+      }
+    }
+    let is_match = match self.condition {
+      Condition::And => !matched.is_empty() && matched.len() == word.words.len(),
+      Condition::Or => !matched.is_empty(),
+    };
+    (is_match, matched)
+  }
+
+  pub fn match_regex(&self, re: &Regex, _words: Vec<String>, body: String) -> (bool, Vec<String>) {
+    let mut matched = Vec::new();
+    for pattern in re.patterns() {
+      if let Some(m) = pattern.find(&body) {
+        matched.push(m.as_str().to_string());
+      }
+    }
+    let is_match = match self.condition {
+      Condition::And => !matched.is_empty() && matched.len() == re.patterns().len(),
+      Condition::Or => !matched.is_empty(),
+    };
+    (is_match, matched)
+  }
+
+  pub fn match_status_code(&self, status: &[u16], code: u16) -> bool {
+    status.contains(&code)
+  }
+
+  /// Matches hex-encoded byte patterns against the target's raw (non-UTF8)
+  /// body, e.g. binary protocol banners from `tcp_default`/`tcp_other`
+  /// templates. Reports each matched pattern as its hex string.
+  pub fn match_binary(&self, bin: &Binary, raw: &[u8]) -> (bool, Vec<String>) {
+    let mut matched = Vec::new();
+    for pattern in &bin.binary {
+      let Ok(needle) = hex::decode(pattern.hex()) else {
+        continue;
+      };
+      if Binary::matches_at(raw, &needle, pattern.offset()) {
+        matched.push(pattern.hex().to_string());
+      }
+    }
+    let is_match = match self.condition {
+      Condition::And => !matched.is_empty() && matched.len() == bin.binary.len(),
+      Condition::Or => !matched.is_empty(),
+    };
+    (is_match, matched)
+  }
+
+  pub fn match_favicon(&self, fav: &str, hm: &BTreeMap<String, FaviconMap>) -> (bool, Vec<String>) {
+    match hm.get(fav) {
+      Some(map) => (true, vec![map.name.clone()]),
+      None => (false, Vec::new()),
+    }
+  }
+
+  /// Parses `body` as JSON once and applies every `JsonMatch` entry's rule
+  /// (`equals`/`type`/`regex`/`arrayLength`/`exists`) to whatever its
+  /// JSONPath resolves to, aggregating pass/fail across entries like the
+  /// other matcher types do.
+  pub fn match_json(&self, json: &Json, body: &str) -> (bool, Vec<String>) {
+    let Ok(root) = serde_json::from_str::<serde_json::Value>(body) else {
+      return (false, Vec::new());
+    };
+    let mut matched = Vec::new();
+    for entry in &json.json {
+      let node = jsonpath::resolve(&root, &entry.path);
+      let passed = match (&entry.rule, node) {
+        (JsonRule::Exists, node) => node.is_some(),
+        (JsonRule::Equals(expected), Some(value)) => value == expected,
+        (JsonRule::Type(ty), Some(value)) => ty.matches(value),
+        (JsonRule::Regex(_), Some(value)) => entry
+          .compiled_regex
+          .as_ref()
+          .map(|re| re.is_match(&json_scalar_string(value)))
+          .unwrap_or(false),
+        (JsonRule::ArrayLength(rule), Some(serde_json::Value::Array(arr))) => rule.matches(arr.len()),
+        _ => false,
+      };
+      if passed {
+        matched.push(entry.path.clone());
+      }
+    }
+    let is_match = match self.condition {
+      Condition::And => !matched.is_empty() && matched.len() == json.json.len(),
+      Condition::Or => !matched.is_empty(),
+    };
+    (is_match, matched)
+  }
+
+  /// Parses `body` as HTML/XML (best-effort) and reports a match when every
+  /// (And) or any (Or) compiled XPath expression resolves to a non-empty
+  /// node-set. `cache` is shared across an entire `ClusteredOperator` pass,
+  /// so a body already parsed for another clustered template's matcher is
+  /// reused instead of re-parsed.
+  pub fn match_xpath(&self, xp: &XPath, body: &str, cache: &xpath::XPathCache) -> (bool, Vec<String>) {
+    let doc = cache.get_or_parse(body);
+    let mut matched = Vec::new();
+    let mut matched_count = 0;
+    for expr in xp.expressions() {
+      let values = expr.evaluate(&doc);
+      if !values.is_empty() {
+        matched_count += 1;
+        matched.extend(values);
+      }
+    }
+    let is_match = match self.condition {
+      Condition::And => matched_count > 0 && matched_count == xp.expressions().len(),
+      Condition::Or => matched_count > 0,
+    };
+    (is_match, matched)
+  }
+
+  /// Evaluates every expression in `dsl` against `target`, reporting the
+  /// raw source of each expression that came back truthy. Expressions that
+  /// fail to evaluate (unbound variable, type mismatch) are treated as
+  /// non-matching rather than propagated as errors.
+  pub fn match_dsl<T: OperatorTarget>(
+    &self,
+    dsl: &Dsl,
+    target: &T,
+    status_code: Option<u16>,
+    extract_result: &BTreeMap<String, HashSet<String>>,
+  ) -> (bool, Vec<String>) {
+    let mut matched = Vec::new();
+    for expr in dsl.expressions() {
+      // Succeeds when the expression evaluates truthy, not just when it
+      // literally returns `true` - `len(body)` or a bare header lookup
+      // should match like `&&`/`||`/`!` already treat them.
+      if expr.eval(target, status_code, extract_result).is_ok_and(|value| value.as_bool().unwrap_or(false)) {
+        matched.push(expr.raw().to_string());
+      }
+    }
+    let is_match = match self.condition {
+      Condition::And => !matched.is_empty() && matched.len() == dsl.expressions().len(),
+      Condition::Or => !matched.is_empty(),
+    };
+    (is_match, matched)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_match_binary_anywhere_and_at_offset() {
+    let haystack = b"\x00\x01\xde\xad\xbe\xef\x00";
+    let matcher = Matcher::default();
+
+    let anywhere = Binary { binary: vec![BinaryPattern::Hex("deadbeef".to_string())] };
+    let (is_match, matched) = matcher.match_binary(&anywhere, haystack);
+    assert!(is_match);
+    assert_eq!(matched, vec!["deadbeef".to_string()]);
+
+    let right_offset = Binary { binary: vec![BinaryPattern::WithOffset { binary: "deadbeef".to_string(), offset: 2 }] };
+    assert!(matcher.match_binary(&right_offset, haystack).0);
+
+    let wrong_offset = Binary { binary: vec![BinaryPattern::WithOffset { binary: "deadbeef".to_string(), offset: 0 }] };
+    assert!(!matcher.match_binary(&wrong_offset, haystack).0);
+
+    // A huge offset must not overflow `offset + needle.len()`.
+    let overflowing_offset =
+      Binary { binary: vec![BinaryPattern::WithOffset { binary: "deadbeef".to_string(), offset: usize::MAX - 1 }] };
+    assert!(!matcher.match_binary(&overflowing_offset, haystack).0);
+  }
+
+  #[test]
+  fn test_match_binary_each_pattern_keeps_its_own_offset() {
+    // Two markers pinned to different fixed offsets in the same banner -
+    // expressible only because each pattern carries its own offset.
+    let haystack = b"\xca\xfe\x00\x00\xde\xad\xbe\xef";
+    let matcher = Matcher { condition: Condition::And, ..Default::default() };
+
+    let bin = Binary {
+      binary: vec![
+        BinaryPattern::WithOffset { binary: "cafe".to_string(), offset: 0 },
+        BinaryPattern::WithOffset { binary: "deadbeef".to_string(), offset: 4 },
+      ],
+    };
+    let (is_match, matched) = matcher.match_binary(&bin, haystack);
+    assert!(is_match);
+    assert_eq!(matched, vec!["cafe".to_string(), "deadbeef".to_string()]);
+
+    // Swapping which pattern owns which offset must fail instead of still
+    // matching "anywhere".
+    let swapped = Binary {
+      binary: vec![
+        BinaryPattern::WithOffset { binary: "cafe".to_string(), offset: 4 },
+        BinaryPattern::WithOffset { binary: "deadbeef".to_string(), offset: 0 },
+      ],
+    };
+    assert!(!matcher.match_binary(&swapped, haystack).0);
+  }
+
+  #[test]
+  fn test_match_json_rules_aggregate() {
+    let body = r#"{"version":"1.2.3","count":2,"tags":["a","b","c"],"ok":true}"#;
+
+    let mut json = Json {
+      json: vec![
+        JsonMatch { path: "$.version".to_string(), rule: JsonRule::Equals(serde_json::json!("1.2.3")), compiled_regex: None },
+        JsonMatch { path: "$.count".to_string(), rule: JsonRule::Type(JsonValueType::Number), compiled_regex: None },
+        JsonMatch { path: "$.version".to_string(), rule: JsonRule::Regex(r"^1\.\d+\.\d+$".to_string()), compiled_regex: None },
+        JsonMatch { path: "$.tags".to_string(), rule: JsonRule::ArrayLength(ArrayLengthRule::Exact(3)), compiled_regex: None },
+        JsonMatch { path: "$.ok".to_string(), rule: JsonRule::Exists, compiled_regex: None },
+      ],
+    };
+    json.compile().unwrap();
+
+    let and_matcher = Matcher { condition: Condition::And, ..Default::default() };
+    let (is_match, matched) = and_matcher.match_json(&json, body);
+    assert!(is_match);
+    assert_eq!(matched.len(), 5);
+
+    // A missing path fails every rule and, under And, fails the whole group.
+    json.json.push(JsonMatch { path: "$.missing".to_string(), rule: JsonRule::Exists, compiled_regex: None });
+    let (is_match, _) = and_matcher.match_json(&json, body);
+    assert!(!is_match);
+  }
+
+  #[test]
+  fn test_array_length_rule_comparisons() {
+    assert!(ArrayLengthRule::Exact(3).matches(3));
+    assert!(!ArrayLengthRule::Exact(3).matches(4));
+    let gte = ArrayLengthRule::Cmp { eq: None, gte: Some(3), lte: None, gt: None, lt: None };
+    assert!(gte.matches(3));
+    assert!(gte.matches(5));
+    assert!(!gte.matches(2));
+  }
+
+  #[test]
+  fn test_json_compile_rejects_empty_array_length_cmp() {
+    // `arrayLength: {}` deserializes fine (every Cmp field is optional) but
+    // must not be allowed to silently match any array length.
+    let mut json = Json {
+      json: vec![JsonMatch {
+        path: "$.tags".to_string(),
+        rule: JsonRule::ArrayLength(ArrayLengthRule::Cmp { eq: None, gte: None, lte: None, gt: None, lt: None }),
+        compiled_regex: None,
+      }],
+    };
+    assert!(json.compile().is_err());
+  }
+}
@@ -1,18 +1,22 @@
 use crate::error::{Error, Result, new_regex_error};
 use crate::info::Version;
 use crate::operators::extractors::{Extractor, ExtractorType};
-use crate::operators::matchers::{Condition, FaviconMap, Matcher, MatcherType};
+use crate::operators::matchers::{Condition, FaviconMap, Matcher, MatcherType, Part};
 use crate::operators::target::OperatorTarget;
+use crate::operators::xpath::XPathCache;
 use crate::serde_format::is_default;
 use serde::{Deserialize, Serialize};
 use slinger::Response;
 use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
 
+pub mod dsl;
 pub mod extractors;
+pub mod jsonpath;
 pub mod matchers;
 pub mod regex;
 pub mod target;
+pub mod xpath;
 
 /// Operators for the current request go here.
 #[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
@@ -72,6 +76,19 @@ pub struct Operators {
     )
   )]
   pub extractors: Vec<Arc<Extractor>>,
+  // description: |
+  //   Diagnostics enables collecting a MatchTrace for every matcher evaluated,
+  //   explaining why each one passed or failed. Off by default so the hot
+  //   matching path stays allocation-free.
+  #[serde(default, skip_serializing_if = "is_default")]
+  #[cfg_attr(
+    feature = "mcp",
+    schemars(
+      title = "collect per-matcher diagnostics",
+      description = "Record why each matcher passed or failed instead of only the aggregate result"
+    )
+  )]
+  pub diagnostics: bool,
 }
 
 impl Operators {
@@ -92,8 +109,11 @@ impl Operators {
     &self,
     version: Option<Version>,
     target: &T,
+    response_for_extensions: Option<&Response>,
     result: &mut OperatorResult,
+    xpath_cache: &XPathCache,
   ) {
+    let status_code = response_for_extensions.map(|response| response.status_code().as_u16());
     for (index, extractor) in self.extractors.iter().enumerate() {
       let (words, body) =
         if let Ok((words, body)) = extractor.part.get_matcher_word_from_part(target) {
@@ -101,12 +121,29 @@ impl Operators {
         } else {
           continue;
         };
+      // KVal yields one bucket per configured key (e.g. several headers in
+      // one extractor), so it's handled separately from the single-bucket
+      // extractors below: a named extractor merges every key's values under
+      // its name, an unnamed one stores each under its own kval key.
+      if let ExtractorType::KVal(kval) = &extractor.extractor_type {
+        for (kval_key, values) in extractor.extract_kval(kval, target) {
+          if values.is_empty() {
+            continue;
+          }
+          let key = extractor.name.clone().unwrap_or(kval_key);
+          result.extract_result.entry(key).or_default().extend(values);
+        }
+        continue;
+      }
       let (extract_result, version) = match &extractor.extractor_type {
         ExtractorType::Regex(re) => extractor.extract_regex(re, words, body, &version),
         ExtractorType::JSON(json) => extractor.extract_json(json, words),
-        ExtractorType::KVal(..) | ExtractorType::XPath(..) | ExtractorType::DSL(..) => {
-          (HashSet::new(), BTreeMap::new())
-        }
+        ExtractorType::DSL(dsl) => (
+          extractor.extract_dsl(dsl, target, status_code, &result.extract_result),
+          BTreeMap::new(),
+        ),
+        ExtractorType::XPath(xp) => (extractor.extract_xpath(xp, &body, xpath_cache), BTreeMap::new()),
+        ExtractorType::KVal(..) => unreachable!("handled above"),
       };
       if !extract_result.is_empty() {
         let key = extractor.name.clone().unwrap_or(index.to_string());
@@ -127,10 +164,11 @@ impl Operators {
     version: Option<Version>,
     response: &Response,
     result: &mut OperatorResult,
+    xpath_cache: &XPathCache,
   ) {
-    self.extractor_generic(version, response, result)
+    self.extractor_generic(version, response, Some(response), result, xpath_cache)
   }
-  
+
   /// Generic matcher that works with any OperatorTarget (Response or Request)
   /// For Response, it can access extensions for favicon and status code
   /// For Request, status code matching will be skipped
@@ -139,16 +177,33 @@ impl Operators {
     target: &T,
     response_for_extensions: Option<&Response>,
     result: &mut OperatorResult,
+    xpath_cache: &XPathCache,
   ) -> Result<()> {
     let mut matchers = Vec::new();
     if self.matchers.is_empty() {
       return Ok(());
     }
+    // Once an `And` cluster has failed, the rest of its matchers only run on
+    // to fill out `match_trace` (when diagnostics is on) - their `mw`/`name`
+    // must not leak into the already-decided non-trace fields below.
+    let mut failed_and = false;
     for matcher in self.matchers.iter() {
       // extract matcher word from target parts
       let (words, body) = matcher.part.get_matcher_word_from_part(target)?;
+      // Only ever built when diagnostics are requested, so the hot matching
+      // path stays allocation-free. Each arm records the value it actually
+      // compared against - the response status code for `Status`, the
+      // favicon hash for `Favicon`, etc. - rather than the generic part
+      // slice, so e.g. a failing `Status` matcher reports the real status
+      // code instead of the response body.
+      let mut diag_actual = String::new();
       let (is_match, mw) = match &matcher.matcher_type {
-        MatcherType::Word(word) => matcher.match_word(word, words),
+        MatcherType::Word(word) => {
+          if self.diagnostics {
+            diag_actual = words.join(", ");
+          }
+          matcher.match_word(word, words)
+        }
         MatcherType::Favicon(fav) => {
           // Favicon matching requires response extensions
           if let Some(response) = response_for_extensions {
@@ -159,6 +214,9 @@ impl Operators {
                 std::io::ErrorKind::InvalidData,
                 "not found favicon",
               )))?;
+            if self.diagnostics {
+              diag_actual = hm.get(fav).map(|m| m.hash.clone()).unwrap_or_else(|| "not found".to_string());
+            }
             matcher.match_favicon(fav, hm)
           } else {
             (false, Vec::new())
@@ -167,32 +225,80 @@ impl Operators {
         MatcherType::Status(status) => {
           // Status code matching only works for Response
           if let Some(response) = response_for_extensions {
-            (
-              matcher.match_status_code(status, response.status_code().as_u16()),
-              vec![response.status_code().as_u16().to_string()],
-            )
+            let code = response.status_code().as_u16();
+            if self.diagnostics {
+              diag_actual = code.to_string();
+            }
+            (matcher.match_status_code(status, code), vec![code.to_string()])
           } else {
             (false, Vec::new())
           }
         }
-        MatcherType::Regex(re) => matcher.match_regex(re, words, body),
-        MatcherType::None
-        | MatcherType::DSL(..)
-        | MatcherType::Binary(..)
-        | MatcherType::XPath(..) => (false, Vec::new()),
+        MatcherType::Regex(re) => {
+          if self.diagnostics {
+            diag_actual = body.clone();
+          }
+          matcher.match_regex(re, words, body)
+        }
+        MatcherType::DSL(dsl) => {
+          let status_code = response_for_extensions.map(|response| response.status_code().as_u16());
+          let (is_match, mw) = matcher.match_dsl(dsl, target, status_code, &result.extract_result);
+          if self.diagnostics {
+            diag_actual = if mw.is_empty() { "<no truthy expression>".to_string() } else { mw.join(", ") };
+          }
+          (is_match, mw)
+        }
+        MatcherType::XPath(xp) => {
+          if self.diagnostics {
+            diag_actual = body.clone();
+          }
+          matcher.match_xpath(xp, &body, xpath_cache)
+        }
+        MatcherType::JSON(json) => {
+          if self.diagnostics {
+            diag_actual = body.clone();
+          }
+          matcher.match_json(json, &body)
+        }
+        MatcherType::Binary(bin) => {
+          let raw = target.get_raw_body();
+          if self.diagnostics {
+            diag_actual = hex::encode(&raw);
+          }
+          matcher.match_binary(bin, &raw)
+        }
+        MatcherType::None => (false, Vec::new()),
       };
       // normalize negative match
       let is_match = matcher.negative(is_match);
+      if self.diagnostics {
+        let (matcher_type, expected) = matcher.describe();
+        result.match_trace.push(MatchTrace {
+          part: matcher.part.clone(),
+          matcher_type: matcher_type.to_string(),
+          expected,
+          actual: diag_actual.chars().take(200).collect(),
+          passed: is_match,
+        });
+      }
       matchers.push(is_match);
       if !is_match {
         match self.matchers_condition {
           Condition::Or => continue,
           Condition::And => {
             result.matched = false;
-            break;
+            failed_and = true;
+            // Normally short-circuits here, since the And outcome is
+            // already decided. With diagnostics on, keep evaluating the
+            // remaining matchers anyway so their traces are recorded too -
+            // otherwise an author only ever sees the first failure in an
+            // And cluster, not the full picture of every near-miss.
+            if !self.diagnostics {
+              break;
+            }
           }
         }
-      } else {
+      } else if !failed_and {
         if let Some(name) = &matcher.name {
           result.name.insert(name.clone());
         }
@@ -212,8 +318,8 @@ impl Operators {
   }
   
   /// 匹配接口统一为只接收 &Response，request 可通过 response.extensions().get::<Request>() 访问
-  pub fn matcher(&self, response: &Response, result: &mut OperatorResult) -> Result<()> {
-    self.matcher_generic(response, Some(response), result)
+  pub fn matcher(&self, response: &Response, result: &mut OperatorResult, xpath_cache: &XPathCache) -> Result<()> {
+    self.matcher_generic(response, Some(response), result, xpath_cache)
   }
 }
 
@@ -264,6 +370,29 @@ pub struct OperatorResult {
     )
   )]
   extract_result: BTreeMap<String, HashSet<String>>,
+  /// Description: Per-matcher diagnostics, populated only when `Operators::diagnostics` is set
+  /// Example: [{"part": "header", "matcher-type": "status", "expected": "200", "actual": "403", "passed": false}]
+  #[cfg_attr(
+    feature = "mcp",
+    schemars(
+      title = "Match Trace",
+      description = "Per-matcher diagnostics explaining why each matcher passed or failed",
+      example = r#"[{"part": "header", "matcher-type": "status", "expected": "200", "actual": "403", "passed": false}]"#
+    )
+  )]
+  match_trace: Vec<MatchTrace>,
+}
+
+/// A single matcher's pass/fail explanation, collected when `Operators::diagnostics` is enabled.
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct MatchTrace {
+  pub part: Part,
+  pub matcher_type: String,
+  pub expected: String,
+  pub actual: String,
+  pub passed: bool,
 }
 
 impl OperatorResult {
@@ -287,6 +416,9 @@ impl OperatorResult {
   pub fn extract_result(&self) -> BTreeMap<String, HashSet<String>> {
     self.extract_result.clone()
   }
+  pub fn match_trace(&self) -> &[MatchTrace] {
+    &self.match_trace
+  }
 }
 
 #[cfg(test)]
@@ -380,7 +512,7 @@ mod tests {
     // Match against the request
     let mut result = OperatorResult::default();
     operators
-      .matcher_generic(&request, None, &mut result)
+      .matcher_generic(&request, None, &mut result, &XPathCache::default())
       .unwrap();
 
     // Verify the match
@@ -418,11 +550,61 @@ mod tests {
     // Match against the response
     let mut result = OperatorResult::default();
     operators
-      .matcher_generic(&response, Some(&response), &mut result)
+      .matcher_generic(&response, Some(&response), &mut result, &XPathCache::default())
       .unwrap();
 
     // Verify the match
     assert!(result.is_matched());
     assert!(result.name.contains("apache-server"));
   }
+
+  #[test]
+  fn test_diagnostics_records_match_trace_for_every_and_matcher() {
+    // Create a response whose status doesn't satisfy the Status matcher, so
+    // the And cluster fails on the first matcher.
+    let response = slinger::http::Response::builder()
+      .status(403)
+      .body(slinger::Body::from("nope"))
+      .unwrap();
+    let response = Response::from(response);
+
+    let mut status_matcher = Matcher {
+      matcher_type: MatcherType::Status(vec![200]),
+      part: Part::Header,
+      ..Default::default()
+    };
+    status_matcher.compile().unwrap();
+
+    let mut word_matcher = Matcher {
+      matcher_type: MatcherType::Word(Word { words: vec!["nope".to_string()] }),
+      part: Part::Body,
+      ..Default::default()
+    };
+    word_matcher.compile().unwrap();
+
+    let operators = Operators {
+      matchers: vec![Arc::new(status_matcher), Arc::new(word_matcher)],
+      matchers_condition: Condition::And,
+      diagnostics: true,
+      ..Default::default()
+    };
+
+    let mut result = OperatorResult::default();
+    operators
+      .matcher_generic(&response, Some(&response), &mut result, &XPathCache::default())
+      .unwrap();
+
+    // The cluster fails (status doesn't match), but with diagnostics on, the
+    // word matcher after it still gets evaluated and traced instead of the
+    // loop stopping at the first failure.
+    assert!(!result.is_matched());
+    let traces = result.match_trace();
+    assert_eq!(traces.len(), 2);
+    assert_eq!(traces[0].matcher_type, "status");
+    assert_eq!(traces[0].expected, "200");
+    assert_eq!(traces[0].actual, "403");
+    assert!(!traces[0].passed);
+    assert_eq!(traces[1].matcher_type, "word");
+    assert!(traces[1].passed);
+  }
 }
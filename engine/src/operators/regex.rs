@@ -0,0 +1,34 @@
+use regex::Regex as CompiledRegex;
+use serde::{Deserialize, Serialize};
+
+/// A list of regex patterns, serialized as their source strings and compiled
+/// lazily via [`Regex::compile`].
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Regex {
+  pub regex: Vec<String>,
+  #[serde(skip)]
+  #[cfg_attr(feature = "mcp", schemars(skip))]
+  compiled: Vec<CompiledRegex>,
+}
+
+impl PartialEq for Regex {
+  fn eq(&self, other: &Self) -> bool {
+    self.regex == other.regex
+  }
+}
+
+impl Regex {
+  pub fn compile(&mut self) -> Result<(), regex::Error> {
+    self.compiled = self
+      .regex
+      .iter()
+      .map(|pattern| CompiledRegex::new(pattern))
+      .collect::<Result<Vec<_>, _>>()?;
+    Ok(())
+  }
+
+  pub fn patterns(&self) -> &[CompiledRegex] {
+    &self.compiled
+  }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+
+/// Uniform view over whatever we're matching/extracting against, so the same
+/// `Operators` can run against a `slinger::Request` or a `slinger::Response`.
+pub trait OperatorTarget {
+  /// Each header rendered as `"name: value"`, lower-cased name.
+  fn get_headers(&self) -> HashSet<String>;
+  /// Single header value looked up case-insensitively. When a header is
+  /// repeated (e.g. `Set-Cookie`), returns only the first occurrence - use
+  /// [`OperatorTarget::get_header_values`] to see every occurrence.
+  fn get_header(&self, name: &str) -> Option<String>;
+  /// Every value for a header looked up case-insensitively, in the order
+  /// they appear. Servers send one `Set-Cookie` header per cookie, so this
+  /// is what cookie extraction needs instead of `get_header`.
+  fn get_header_values(&self, name: &str) -> Vec<String>;
+  /// Body decoded as UTF-8 (lossy). Returns `None` when there is no body.
+  fn get_body(&self) -> Option<String>;
+  /// Raw body bytes, for targets that aren't valid UTF-8 (binary protocol banners).
+  fn get_raw_body(&self) -> Vec<u8>;
+}
+
+impl OperatorTarget for slinger::Request {
+  fn get_headers(&self) -> HashSet<String> {
+    self
+      .headers()
+      .iter()
+      .map(|(k, v)| format!("{}: {}", k.as_str().to_lowercase(), v.to_str().unwrap_or_default()))
+      .collect()
+  }
+
+  fn get_header(&self, name: &str) -> Option<String> {
+    self
+      .headers()
+      .iter()
+      .find(|(k, _)| k.as_str().eq_ignore_ascii_case(name))
+      .and_then(|(_, v)| v.to_str().ok().map(|s| s.to_string()))
+  }
+
+  fn get_header_values(&self, name: &str) -> Vec<String> {
+    self
+      .headers()
+      .iter()
+      .filter(|(k, _)| k.as_str().eq_ignore_ascii_case(name))
+      .filter_map(|(_, v)| v.to_str().ok().map(|s| s.to_string()))
+      .collect()
+  }
+
+  fn get_body(&self) -> Option<String> {
+    self.body().map(|b| String::from_utf8_lossy(b.as_bytes()).to_string())
+  }
+
+  fn get_raw_body(&self) -> Vec<u8> {
+    self.body().map(|b| b.as_bytes().to_vec()).unwrap_or_default()
+  }
+}
+
+impl OperatorTarget for slinger::Response {
+  fn get_headers(&self) -> HashSet<String> {
+    self
+      .headers()
+      .iter()
+      .map(|(k, v)| format!("{}: {}", k.as_str().to_lowercase(), v.to_str().unwrap_or_default()))
+      .collect()
+  }
+
+  fn get_header(&self, name: &str) -> Option<String> {
+    self
+      .headers()
+      .iter()
+      .find(|(k, _)| k.as_str().eq_ignore_ascii_case(name))
+      .and_then(|(_, v)| v.to_str().ok().map(|s| s.to_string()))
+  }
+
+  fn get_header_values(&self, name: &str) -> Vec<String> {
+    self
+      .headers()
+      .iter()
+      .filter(|(k, _)| k.as_str().eq_ignore_ascii_case(name))
+      .filter_map(|(_, v)| v.to_str().ok().map(|s| s.to_string()))
+      .collect()
+  }
+
+  fn get_body(&self) -> Option<String> {
+    self.body().map(|b| String::from_utf8_lossy(b.as_bytes()).to_string())
+  }
+
+  fn get_raw_body(&self) -> Vec<u8> {
+    self.body().map(|b| b.as_bytes().to_vec()).unwrap_or_default()
+  }
+}
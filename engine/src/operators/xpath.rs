@@ -0,0 +1,231 @@
+//! XPath matcher/extractor support for XML/HTML response bodies.
+//!
+//! Bodies are parsed with an HTML-tolerant parser (malformed markup degrades
+//! to a best-effort tree rather than erroring the whole template). An earlier
+//! revision kept a process-global cache keyed by a 64-bit body hash, but that
+//! leaked memory for the life of the process (every distinct body ever
+//! scanned stayed resident) and a hash collision could silently hand back
+//! another response's document. [`XPathCache`] replaces it with a cache
+//! scoped to a single `ClusteredOperator` pass over one response: a
+//! `ClusteredOperator` commonly runs many clustered templates' XPath
+//! matchers/extractors against the same body, so parsing it once per pass
+//! instead of once per matcher avoids the quadratic re-parsing without
+//! keeping anything alive past that pass.
+use crate::error::Error;
+use skyscraper::html;
+use skyscraper::xpath::{self, Xpath};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Parses `body` into an HTML document.
+pub fn parse(body: &str) -> html::HtmlDocument {
+  // `html::parse` tolerates malformed markup, producing a best-effort tree
+  // instead of failing, which is what real-world response bodies need.
+  html::parse(body).unwrap_or_else(|_| html::parse("").expect("empty document always parses"))
+}
+
+/// Caches parsed `HtmlDocument`s for the lifetime of a single
+/// `ClusteredOperator::matcher`/`matcher_request` pass, keyed by the exact
+/// body text handed to `match_xpath`/`extract_xpath`. Create one per pass and
+/// drop it afterwards - it isn't meant to outlive the response it was built
+/// for.
+#[derive(Debug, Default)]
+pub struct XPathCache(RefCell<HashMap<String, Rc<html::HtmlDocument>>>);
+
+impl XPathCache {
+  /// Returns the cached document for `body`, parsing and caching it on the
+  /// first call for that text.
+  pub fn get_or_parse(&self, body: &str) -> Rc<html::HtmlDocument> {
+    if let Some(doc) = self.0.borrow().get(body) {
+      return doc.clone();
+    }
+    let doc = Rc::new(parse(body));
+    self.0.borrow_mut().insert(body.to_string(), doc.clone());
+    doc
+  }
+}
+
+fn qualify(pattern: &str, namespaces: &BTreeMap<String, String>) -> String {
+  if namespaces.is_empty() {
+    return pattern.to_string();
+  }
+  // `skyscraper`'s xpath parser has no namespace-aware resolution step, so we
+  // inline prefix -> URI substitutions into the path text itself. A blind
+  // string replace would also rewrite a `prefix:` substring sitting inside a
+  // quoted literal (e.g. `@href="http://example.com"`), so only the text
+  // outside string literals is substituted - quoted spans are carried
+  // through untouched.
+  let mut qualified = String::with_capacity(pattern.len());
+  let mut rest = pattern;
+  while let Some(idx) = rest.find(['\'', '"']) {
+    let (before, from_quote) = rest.split_at(idx);
+    qualified.push_str(&substitute_namespaces(before, namespaces));
+    let quote = from_quote.chars().next().expect("find() guarantees a match");
+    match from_quote[quote.len_utf8()..].find(quote) {
+      Some(end) => {
+        let literal_end = quote.len_utf8() + end + quote.len_utf8();
+        qualified.push_str(&from_quote[..literal_end]);
+        rest = &from_quote[literal_end..];
+      }
+      None => {
+        // Unterminated literal; carry the remainder through untouched and
+        // let `xpath::parse` report the syntax error.
+        qualified.push_str(from_quote);
+        rest = "";
+      }
+    }
+  }
+  qualified.push_str(&substitute_namespaces(rest, namespaces));
+  qualified
+}
+
+fn substitute_namespaces(segment: &str, namespaces: &BTreeMap<String, String>) -> String {
+  let mut out = segment.to_string();
+  for (prefix, uri) in namespaces {
+    out = replace_qualified_prefix(&out, prefix, uri);
+  }
+  out
+}
+
+/// Replaces `prefix:` with `{uri}`, but only where `prefix:` is actually a
+/// QName prefix - not a substring straddling an axis separator like
+/// `child::` or `following::` (a short prefix such as `d` or `g` lines up
+/// with the last letter of an axis name right before the `::`), and not a
+/// substring sitting inside a longer identifier (e.g. prefix `tom` inside
+/// `atom:title`). A QName prefix always starts at a name boundary (start of
+/// the segment, or a character that can't appear inside an XPath name) and
+/// its colon is never itself followed by another colon.
+fn replace_qualified_prefix(segment: &str, prefix: &str, uri: &str) -> String {
+  let needle = format!("{prefix}:");
+  let mut out = String::with_capacity(segment.len());
+  let mut rest = segment;
+  while let Some(idx) = rest.find(&needle) {
+    let (before, from_match) = rest.split_at(idx);
+    let is_boundary = before.chars().next_back().map_or(true, |c| !is_xpath_name_char(c));
+    let match_end = needle.len();
+    let followed_by_colon = from_match[match_end..].starts_with(':');
+    out.push_str(before);
+    if is_boundary && !followed_by_colon {
+      out.push_str(&format!("{{{uri}}}"));
+    } else {
+      out.push_str(&from_match[..match_end]);
+    }
+    rest = &from_match[match_end..];
+  }
+  out.push_str(rest);
+  out
+}
+
+/// Characters that can appear inside an XPath NCName, used to tell a genuine
+/// prefix boundary from a match that straddles a longer identifier.
+fn is_xpath_name_char(c: char) -> bool {
+  c.is_alphanumeric() || c == '_' || c == '-' || c == '.'
+}
+
+/// A single compiled XPath expression, ready for repeated evaluation.
+#[derive(Debug, Clone)]
+pub struct CompiledXPath {
+  raw: String,
+  expr: Xpath,
+}
+
+impl CompiledXPath {
+  pub fn compile(pattern: &str, namespaces: &BTreeMap<String, String>) -> Result<Self, Error> {
+    let qualified = qualify(pattern, namespaces);
+    let expr = xpath::parse(&qualified)
+      .map_err(|e| Error::IO(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("invalid xpath `{pattern}`: {e}"))))?;
+    Ok(Self { raw: pattern.to_string(), expr })
+  }
+
+  pub fn raw(&self) -> &str {
+    &self.raw
+  }
+
+  /// String value of every node/attribute the expression resolves to.
+  pub fn evaluate(&self, doc: &html::HtmlDocument) -> Vec<String> {
+    self
+      .expr
+      .apply(doc)
+      .map(|nodes| nodes.into_iter().map(|node| node.get_text(doc).unwrap_or_default()).collect())
+      .unwrap_or_default()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_qualify_substitutes_prefix_outside_literals_only() {
+    let namespaces = BTreeMap::from([("atom".to_string(), "http://www.w3.org/2005/Atom".to_string())]);
+
+    // Outside a literal, `atom:` is a namespace prefix and gets substituted.
+    let qualified = qualify("//atom:entry/atom:title", &namespaces);
+    assert_eq!(qualified, "//{http://www.w3.org/2005/Atom}entry/{http://www.w3.org/2005/Atom}title");
+
+    // Inside a quoted literal, a colon-bearing substring (e.g. a URL) must
+    // survive untouched even though it happens to contain "atom:".
+    let qualified = qualify(r#"//a[@href="atom:not-a-namespace"]"#, &namespaces);
+    assert_eq!(qualified, r#"//a[@href="atom:not-a-namespace"]"#);
+  }
+
+  #[test]
+  fn test_qualify_does_not_collide_with_axis_separator() {
+    // A short prefix whose letter lines up with the last character of an
+    // axis name, right before `::`, must not be mistaken for a QName prefix.
+    let namespaces = BTreeMap::from([("d".to_string(), "urn:x".to_string())]);
+    assert_eq!(qualify("//child::div", &namespaces), "//child::div");
+
+    let namespaces = BTreeMap::from([("g".to_string(), "urn:y".to_string())]);
+    assert_eq!(qualify("//following::a", &namespaces), "//following::a");
+
+    // A genuine prefix right after an axis separator still qualifies.
+    let namespaces = BTreeMap::from([("atom".to_string(), "http://www.w3.org/2005/Atom".to_string())]);
+    assert_eq!(
+      qualify("//child::atom:title", &namespaces),
+      "//child::{http://www.w3.org/2005/Atom}title"
+    );
+  }
+
+  #[test]
+  fn test_qualify_does_not_match_inside_a_longer_identifier() {
+    // Prefix "tom" must not match the "tom:" tail sitting inside "atom:title".
+    let namespaces = BTreeMap::from([("tom".to_string(), "urn:z".to_string())]);
+    assert_eq!(qualify("//atom:title", &namespaces), "//atom:title");
+  }
+
+  #[test]
+  fn test_qualify_carries_unterminated_literal_through_untouched() {
+    let namespaces = BTreeMap::from([("atom".to_string(), "http://www.w3.org/2005/Atom".to_string())]);
+
+    // The opening quote is never closed; everything from it onward must be
+    // carried through as-is instead of panicking on the missing end index.
+    let qualified = qualify(r#"//a[@href="atom:unterminated"#, &namespaces);
+    assert_eq!(qualified, r#"//a[@href="atom:unterminated"#);
+  }
+
+  #[test]
+  fn test_qualify_is_a_no_op_without_namespaces() {
+    assert_eq!(qualify("//div[@class='atom:x']", &BTreeMap::new()), "//div[@class='atom:x']");
+  }
+
+  #[test]
+  fn test_parse_malformed_markup_falls_back_to_best_effort_tree() {
+    // Unclosed tags and a stray closing tag shouldn't make `parse` error out;
+    // it should hand back whatever best-effort tree the parser recovers.
+    let doc = parse("<html><body><div><p>unclosed</body></html></div>");
+    let compiled = CompiledXPath::compile("//p", &BTreeMap::new()).unwrap();
+    assert_eq!(compiled.evaluate(&doc), vec!["unclosed".to_string()]);
+  }
+
+  #[test]
+  fn test_xpath_cache_reuses_parsed_document_for_identical_body() {
+    let cache = XPathCache::default();
+    let body = "<html><body><p>hi</p></body></html>";
+    let first = cache.get_or_parse(body);
+    let second = cache.get_or_parse(body);
+    assert!(Rc::ptr_eq(&first, &second));
+  }
+}